@@ -10,16 +10,11 @@ async fn real_mainlbl()
     let mut sinbuf = VecRng::<u8>::with_capacity(4096);
     let mut i = stdin();
     let mut o = stdout();
+    let mut rdbuf = [0u8; 4096];
     loop
     {
-        let (mut h, mut b) = sinbuf.spare_capacity_mut();
-        let mut n_read = i.read_buf(&mut h).await.unwrap();
-        if n_read == h.len()
-        {
-            n_read += i.read_buf(&mut b).await.unwrap();
-            /* no reallocation logic :( */
-        }
-        unsafe { sinbuf.back_init_change(n_read as isize); }
+        let n_read = i.read(&mut rdbuf).await.unwrap();
+        sinbuf.extend_from_slice(&rdbuf[..n_read]);
         let (h, b) = sinbuf.as_ref();
         let mut nwritten = o.write(h).await.unwrap();
         if nwritten == h.len()
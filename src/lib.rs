@@ -2,16 +2,46 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::{mem::{MaybeUninit, transmute}, ptr::NonNull};
+#![feature(allocator_api)]
 
-const fn dangling_boxed_slice<T>()
-    -> Box<[T]>
+use std::{alloc::{Allocator, Global, Layout, handle_alloc_error},
+    fmt, mem::MaybeUninit, ptr::NonNull};
+
+const fn dangling_slice<T>()
+    -> NonNull<[MaybeUninit<T>]>
 {
-    let ptr = NonNull::slice_from_raw_parts(NonNull::<T>::dangling(), 0);
-    unsafe { transmute(ptr) }
+    NonNull::slice_from_raw_parts(NonNull::<MaybeUninit<T>>::dangling(), 0)
 }
 
-pub struct VecRng<T>
+/* never panics; returned instead of aborting on OOM so `VecRng` stays
+ * usable in no-panic contexts.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryReserveError
+{
+    CapacityOverflow,
+    AllocError
+    {
+        layout: Layout,
+    },
+}
+impl fmt::Display for TryReserveError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>)
+        -> fmt::Result
+    {
+        match self
+        {
+            Self::CapacityOverflow =>
+                f.write_str("requested capacity exceeds `isize::MAX`"),
+            Self::AllocError { layout } =>
+                write!(f, "allocator failed to allocate {layout:?}"),
+        }
+    }
+}
+impl std::error::Error for TryReserveError {}
+
+pub struct VecRng<T, A: Allocator = Global>
 {
     /* LAYOUT:
      * [ back | uninit | head ]
@@ -20,11 +50,30 @@ pub struct VecRng<T>
      * [ uninit | contig | uninit ]
      * e.g. buffer.len() == 3 && hindex == 1 && length == 1
      */
-    buffer: Box<[MaybeUninit<T>]>,
+    buffer: NonNull<[MaybeUninit<T>]>,
     hindex: usize,
     length: usize,
+    alloc: A,
 }
 impl<T> VecRng<T>
+{
+    pub const fn new()
+        -> Self
+    {
+        Self::new_in(Global)
+    }
+    pub fn with_capacity(c: usize)
+        -> Self
+    {
+        Self::with_capacity_in(c, Global)
+    }
+    pub fn try_with_capacity(c: usize)
+        -> Result<Self, TryReserveError>
+    {
+        Self::try_with_capacity_in(c, Global)
+    }
+}
+impl<T, A: Allocator> VecRng<T, A>
 {
     const MINCAP: usize =
     {
@@ -33,35 +82,87 @@ impl<T> VecRng<T>
         else if siz <= 1024 { 4 }
         else { 1 }
     };
-    pub const fn new()
+    pub const fn new_in(alloc: A)
         -> Self
     {
         Self
         {
-            buffer: dangling_boxed_slice(),
+            buffer: dangling_slice(),
             hindex: 0,
             length: 0,
+            alloc,
         }
     }
-    pub fn with_capacity(c: usize)
+    pub fn with_capacity_in(c: usize, alloc: A)
         -> Self
     {
-        let mut ret = Self::new();
+        let mut ret = Self::new_in(alloc);
         ret.grow(Self::MINCAP.max(c));
         ret
     }
-    fn grow(&mut self, to_c: usize)
+    pub fn try_with_capacity_in(c: usize, alloc: A)
+        -> Result<Self, TryReserveError>
+    {
+        let mut ret = Self::new_in(alloc);
+        ret.try_grow(Self::MINCAP.max(c))?;
+        Ok(ret)
+    }
+    pub const fn allocator(&self)
+        -> &A
+    {
+        &self.alloc
+    }
+    fn layout_of(cap: usize)
+        -> Result<Layout, TryReserveError>
+    {
+        Layout::array::<MaybeUninit<T>>(cap)
+            .map_err(|_| TryReserveError::CapacityOverflow)
+    }
+    /* copies the occupied region into `newbuf`, frees the old buffer
+     * through `self.alloc`, and installs `newbuf` with `hindex == 0`.
+     */
+    fn relocate(&mut self, newbuf: NonNull<[MaybeUninit<T>]>)
     {
-        /* panics if `to_c > isize::MAX` */
-        let mut newbuf = Box::<[T]>::new_uninit_slice(to_c);
         let (hln, bln) = self.lens();
-        let src = self.buffer.as_mut_ptr();
-        let dst = newbuf.as_mut_ptr();
+        let src = self.buffer.as_ptr() as *mut MaybeUninit<T>;
+        let dst = newbuf.as_ptr() as *mut MaybeUninit<T>;
         unsafe { src.copy_to_nonoverlapping(dst.add(hln), bln); }
         unsafe { src.add(self.hindex).copy_to_nonoverlapping(dst, hln); }
+        let ccap = self.buffer.len();
+        if ccap != 0
+        {
+            unsafe
+            {
+                /* `ccap` was successfully laid out when it was allocated,
+                 * so re-deriving its layout here cannot fail. */
+                self.alloc.deallocate(self.buffer.cast(), Self::layout_of(ccap).unwrap());
+            }
+        }
         self.buffer = newbuf;
         self.hindex = 0;
     }
+    fn grow(&mut self, to_c: usize)
+    {
+        /* panics if `to_c` overflows a `Layout`; on allocator failure calls
+         * `handle_alloc_error`, which aborts by default */
+        let new_layout = Self::layout_of(to_c).unwrap();
+        let newptr = self.alloc.allocate(new_layout)
+            .unwrap_or_else(|_| handle_alloc_error(new_layout));
+        self.relocate(NonNull::slice_from_raw_parts(newptr.cast(), to_c));
+    }
+    fn try_grow(&mut self, to_c: usize)
+        -> Result<(), TryReserveError>
+    {
+        if to_c > isize::MAX as usize
+        {
+            return Err(TryReserveError::CapacityOverflow);
+        }
+        let new_layout = Self::layout_of(to_c)?;
+        let newptr = self.alloc.allocate(new_layout)
+            .map_err(|_| TryReserveError::AllocError { layout: new_layout })?;
+        self.relocate(NonNull::slice_from_raw_parts(newptr.cast(), to_c));
+        Ok(())
+    }
     pub fn reserve(&mut self, addc: usize)
     {
         let to_c = addc.checked_add(self.length).unwrap();
@@ -72,6 +173,18 @@ impl<T> VecRng<T>
         }
         self.grow(Self::MINCAP.max(to_c).max(ccap << 1));
     }
+    pub fn try_reserve(&mut self, addc: usize)
+        -> Result<(), TryReserveError>
+    {
+        let to_c = addc.checked_add(self.length)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        let ccap = self.buffer.len();
+        if to_c < ccap
+        {
+            return Ok(());
+        }
+        self.try_grow(Self::MINCAP.max(to_c).max(ccap << 1))
+    }
     /* SAFETY:
      * 0 <= length + n <= buffer.len()
      * all values in the resulting head and back are init
@@ -87,7 +200,7 @@ impl<T> VecRng<T>
          * have sizes greater than `isize::MAX`; thus:
          * isize::MAX >= self.buffer.len() >= self.length > self.hindex
          */
-        ((self.hindex as isize - n) % self.buffer.len() as isize)
+        (self.hindex as isize - n).rem_euclid(self.buffer.len() as isize)
         as usize;
         unsafe { self.back_init_change(n); }
     }
@@ -123,7 +236,7 @@ impl<T> VecRng<T>
         use std::ptr::slice_from_raw_parts as from;
 
         let (hln, bln) = self.lens();
-        let ptr = &raw const *self.buffer as *const T;
+        let ptr = self.buffer.as_ptr() as *const T;
         let h = unsafe { &*from(ptr.add(self.hindex), hln) };
         let b = unsafe { &*from(ptr, bln) };
         (h, b)
@@ -135,7 +248,7 @@ impl<T> VecRng<T>
         use std::ptr::slice_from_raw_parts_mut as from;
 
         let (hln, bln) = self.lens();
-        let ptr = &raw mut *self.buffer as *mut T;
+        let ptr = self.buffer.as_ptr() as *mut T;
         let h = unsafe { &mut *from(ptr.add(self.hindex), hln) };
         let b = unsafe { &mut *from(ptr, bln) };
         (h, b)
@@ -148,7 +261,7 @@ impl<T> VecRng<T>
 
         let (hln, bln) = self.lens();
         let cap = self.buffer.len();
-        let ptr = &raw mut *self.buffer as *mut MaybeUninit<T>;
+        let ptr = self.buffer.as_ptr() as *mut MaybeUninit<T>;
         let a = self.hindex + hln;
         // [ back | uninit | head ] or [ uninit | contig | uninit ]
         //                        ^ here (empty)           ^ here
@@ -157,10 +270,30 @@ impl<T> VecRng<T>
         //          ^ here               ^ here
         &mut *from(ptr.add(bln), self.hindex - bln)) }
     }
-}
-impl<T> Drop for VecRng<T>
-{
-    fn drop(&mut self)
+    /* collapses `(head, back)` into a single contiguous slice, rotating
+     * `hindex` to 0 when the ring currently wraps.
+     *
+     * O(n), allocation-free: `[MaybeUninit<T>]::rotate_left` rotates in
+     * place using at most a small stack buffer.
+     */
+    pub fn make_contiguous(&mut self)
+        -> &mut [T]
+    {
+        use std::ptr::slice_from_raw_parts_mut as from;
+
+        let (_, bln) = self.lens();
+        if bln != 0
+        {
+            let cap = self.buffer.len();
+            let ptr = self.buffer.as_ptr() as *mut MaybeUninit<T>;
+            let whole = unsafe { &mut *from(ptr, cap) };
+            whole.rotate_left(self.hindex);
+            self.hindex = 0;
+        }
+        let ptr = self.buffer.as_ptr() as *mut T;
+        unsafe { &mut *from(ptr.add(self.hindex), self.length) }
+    }
+    fn drop_elements(&mut self)
     {
         let (h, b) = self.as_mut();
         for e in h
@@ -172,4 +305,626 @@ impl<T> Drop for VecRng<T>
             unsafe { (e as *mut T).drop_in_place() }
         }
     }
+    #[inline]
+    pub const fn len(&self)
+        -> usize
+    {
+        self.length
+    }
+    #[inline]
+    pub const fn is_empty(&self)
+        -> bool
+    {
+        self.length == 0
+    }
+    pub fn clear(&mut self)
+    {
+        self.drop_elements();
+        self.hindex = 0;
+        self.length = 0;
+    }
+    pub const fn front(&self)
+        -> Option<&T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let ptr = self.buffer.as_ptr() as *const T;
+        Some(unsafe { &*ptr.add(self.hindex) })
+    }
+    pub const fn front_mut(&mut self)
+        -> Option<&mut T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let ptr = self.buffer.as_ptr() as *mut T;
+        Some(unsafe { &mut *ptr.add(self.hindex) })
+    }
+    pub const fn back(&self)
+        -> Option<&T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let cap = self.buffer.len();
+        let idx = (self.hindex + self.length - 1) % cap;
+        let ptr = self.buffer.as_ptr() as *const T;
+        Some(unsafe { &*ptr.add(idx) })
+    }
+    pub const fn back_mut(&mut self)
+        -> Option<&mut T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let cap = self.buffer.len();
+        let idx = (self.hindex + self.length - 1) % cap;
+        let ptr = self.buffer.as_ptr() as *mut T;
+        Some(unsafe { &mut *ptr.add(idx) })
+    }
+    pub const fn get(&self, index: usize)
+        -> Option<&T>
+    {
+        if index >= self.length
+        {
+            return None;
+        }
+        let cap = self.buffer.len();
+        let idx = (self.hindex + index) % cap;
+        let ptr = self.buffer.as_ptr() as *const T;
+        Some(unsafe { &*ptr.add(idx) })
+    }
+    pub const fn get_mut(&mut self, index: usize)
+        -> Option<&mut T>
+    {
+        if index >= self.length
+        {
+            return None;
+        }
+        let cap = self.buffer.len();
+        let idx = (self.hindex + index) % cap;
+        let ptr = self.buffer.as_ptr() as *mut T;
+        Some(unsafe { &mut *ptr.add(idx) })
+    }
+    pub fn iter(&self)
+        -> Iter<'_, T>
+    {
+        let (head, back) = self.as_ref();
+        Iter { head, back }
+    }
+    pub fn iter_mut(&mut self)
+        -> IterMut<'_, T>
+    {
+        let (head, back) = self.as_mut();
+        IterMut { head, back }
+    }
+    pub fn push_back(&mut self, value: T)
+    {
+        self.reserve(1);
+        let cap = self.buffer.len();
+        let tail = (self.hindex + self.length) % cap;
+        let ptr = self.buffer.as_ptr() as *mut MaybeUninit<T>;
+        unsafe
+        {
+            (*ptr.add(tail)).write(value);
+            self.back_init_change(1);
+        }
+    }
+    pub fn push_front(&mut self, value: T)
+    {
+        self.reserve(1);
+        let cap = self.buffer.len();
+        let idx = (self.hindex + cap - 1) % cap;
+        let ptr = self.buffer.as_ptr() as *mut MaybeUninit<T>;
+        unsafe
+        {
+            (*ptr.add(idx)).write(value);
+            self.head_init_change(1);
+        }
+    }
+    pub fn pop_back(&mut self)
+        -> Option<T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let cap = self.buffer.len();
+        let idx = (self.hindex + self.length - 1) % cap;
+        let ptr = self.buffer.as_ptr() as *const MaybeUninit<T>;
+        let value = unsafe { (*ptr.add(idx)).assume_init_read() };
+        unsafe { self.back_init_change(-1); }
+        Some(value)
+    }
+    pub fn pop_front(&mut self)
+        -> Option<T>
+    {
+        if self.length == 0
+        {
+            return None;
+        }
+        let idx = self.hindex;
+        let ptr = self.buffer.as_ptr() as *const MaybeUninit<T>;
+        let value = unsafe { (*ptr.add(idx)).assume_init_read() };
+        unsafe { self.head_init_change(-1); }
+        Some(value)
+    }
+    /* bulk fast path for `T: Copy`: reserves room for `slice.len()` once and
+     * copies across the two `spare_capacity_mut` halves in at most two
+     * `copy_nonoverlapping` calls, instead of pushing element by element.
+     */
+    pub fn extend_from_slice(&mut self, slice: &[T])
+        where T: Copy
+    {
+        self.reserve(slice.len());
+        let (a, b) = self.spare_capacity_mut();
+        let alen = a.len().min(slice.len());
+        unsafe
+        {
+            slice.as_ptr().copy_to_nonoverlapping(a.as_mut_ptr() as *mut T, alen);
+            slice.as_ptr().add(alen)
+                .copy_to_nonoverlapping(b.as_mut_ptr() as *mut T, slice.len() - alen);
+            self.back_init_change(slice.len() as isize);
+        }
+    }
+}
+impl<T, A: Allocator> Drop for VecRng<T, A>
+{
+    fn drop(&mut self)
+    {
+        self.drop_elements();
+        let cap = self.buffer.len();
+        if cap != 0
+        {
+            unsafe
+            {
+                /* `cap` was successfully laid out when it was allocated,
+                 * so re-deriving its layout here cannot fail. */
+                self.alloc.deallocate(self.buffer.cast(), Self::layout_of(cap).unwrap());
+            }
+        }
+    }
+}
+/* `buffer` is a `NonNull<[MaybeUninit<T>]>`, which is neither `Send` nor
+ * `Sync`, so `VecRng` is not auto-`Send`/`Sync`; but `VecRng` owns its elements and its
+ * allocator exactly like `Box`/`Vec`/`VecDeque` do, so it is safe to send
+ * or share whenever `T` and `A` are, mirroring `alloc::RawVec`.
+ */
+unsafe impl<T: Send, A: Allocator + Send> Send for VecRng<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for VecRng<T, A> {}
+
+/* drops the `written` already-cloned elements at `dst` if dropped while
+ * still armed; disarmed (by forgetting it) once `Clone::clone` finishes
+ * writing every element, so a panic mid-clone does not leak the prefix.
+ */
+struct ClonedPrefix<T>
+{
+    dst: *mut T,
+    written: usize,
+}
+impl<T> Drop for ClonedPrefix<T>
+{
+    fn drop(&mut self)
+    {
+        for i in 0..self.written
+        {
+            unsafe { self.dst.add(i).drop_in_place(); }
+        }
+    }
+}
+impl<T: Clone, A: Allocator + Clone> Clone for VecRng<T, A>
+{
+    fn clone(&self) -> Self
+    {
+        let mut new = Self::with_capacity_in(self.length, self.alloc.clone());
+        let (h, b) = self.as_ref();
+        let dst = new.buffer.as_ptr() as *mut T;
+        let mut guard = ClonedPrefix { dst, written: 0 };
+        for e in h.iter().chain(b)
+        {
+            unsafe { dst.add(guard.written).write(e.clone()); }
+            guard.written += 1;
+        }
+        core::mem::forget(guard);
+        new.length = self.length;
+        new
+    }
+}
+
+/* yields from `head` until exhausted, then from `back`; `next_back`
+ * walks the same two slices in reverse. This is the "ring slices"
+ * pattern also used by `as_ref`/`as_mut`.
+ */
+pub struct Iter<'a, T>
+{
+    head: &'a [T],
+    back: &'a [T],
+}
+impl<'a, T> Iterator for Iter<'a, T>
+{
+    type Item = &'a T;
+    fn next(&mut self)
+        -> Option<&'a T>
+    {
+        if let Some((first, rest)) = self.head.split_first()
+        {
+            self.head = rest;
+            Some(first)
+        }
+        else if let Some((first, rest)) = self.back.split_first()
+        {
+            self.back = rest;
+            Some(first)
+        }
+        else
+        {
+            None
+        }
+    }
+    fn size_hint(&self)
+        -> (usize, Option<usize>)
+    {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+{
+    fn next_back(&mut self)
+        -> Option<&'a T>
+    {
+        if let Some((last, rest)) = self.back.split_last()
+        {
+            self.back = rest;
+            Some(last)
+        }
+        else if let Some((last, rest)) = self.head.split_last()
+        {
+            self.head = rest;
+            Some(last)
+        }
+        else
+        {
+            None
+        }
+    }
+}
+impl<T> ExactSizeIterator for Iter<'_, T>
+{
+    fn len(&self)
+        -> usize
+    {
+        self.head.len() + self.back.len()
+    }
+}
+
+pub struct IterMut<'a, T>
+{
+    head: &'a mut [T],
+    back: &'a mut [T],
+}
+impl<'a, T> Iterator for IterMut<'a, T>
+{
+    type Item = &'a mut T;
+    fn next(&mut self)
+        -> Option<&'a mut T>
+    {
+        let head = core::mem::take(&mut self.head);
+        if let Some((first, rest)) = head.split_first_mut()
+        {
+            self.head = rest;
+            return Some(first);
+        }
+        let back = core::mem::take(&mut self.back);
+        if let Some((first, rest)) = back.split_first_mut()
+        {
+            self.back = rest;
+            return Some(first);
+        }
+        None
+    }
+    fn size_hint(&self)
+        -> (usize, Option<usize>)
+    {
+        let n = self.len();
+        (n, Some(n))
+    }
+}
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T>
+{
+    fn next_back(&mut self)
+        -> Option<&'a mut T>
+    {
+        let back = core::mem::take(&mut self.back);
+        if let Some((last, rest)) = back.split_last_mut()
+        {
+            self.back = rest;
+            return Some(last);
+        }
+        let head = core::mem::take(&mut self.head);
+        if let Some((last, rest)) = head.split_last_mut()
+        {
+            self.head = rest;
+            return Some(last);
+        }
+        None
+    }
+}
+impl<T> ExactSizeIterator for IterMut<'_, T>
+{
+    fn len(&self)
+        -> usize
+    {
+        self.head.len() + self.back.len()
+    }
+}
+
+/* delegates to the already-safe `pop_front`/`pop_back` rather than
+ * duplicating their drop bookkeeping; the wrapped `VecRng` drops
+ * whatever is left un-yielded when `IntoIter` itself is dropped.
+ */
+pub struct IntoIter<T, A: Allocator = Global>(VecRng<T, A>);
+impl<T, A: Allocator> Iterator for IntoIter<T, A>
+{
+    type Item = T;
+    fn next(&mut self)
+        -> Option<T>
+    {
+        self.0.pop_front()
+    }
+    fn size_hint(&self)
+        -> (usize, Option<usize>)
+    {
+        let n = self.0.len();
+        (n, Some(n))
+    }
+}
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A>
+{
+    fn next_back(&mut self)
+        -> Option<T>
+    {
+        self.0.pop_back()
+    }
+}
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A>
+{
+    fn len(&self)
+        -> usize
+    {
+        self.0.len()
+    }
+}
+impl<T, A: Allocator> IntoIterator for VecRng<T, A>
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+    fn into_iter(self)
+        -> IntoIter<T, A>
+    {
+        IntoIter(self)
+    }
+}
+impl<'a, T, A: Allocator> IntoIterator for &'a VecRng<T, A>
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self)
+        -> Iter<'a, T>
+    {
+        self.iter()
+    }
+}
+impl<'a, T, A: Allocator> IntoIterator for &'a mut VecRng<T, A>
+{
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+    fn into_iter(self)
+        -> IterMut<'a, T>
+    {
+        self.iter_mut()
+    }
+}
+/* element-by-element: `reserve`s the size-hint lower bound, then
+ * `push_back`s each item. Without specialization there is no way to
+ * detect a slice-shaped source generically, so this never takes the
+ * bulk `copy_nonoverlapping` path even for `T: Copy` sources; call
+ * `extend_from_slice` directly when the source is already a `&[T]`.
+ */
+impl<T, A: Allocator> Extend<T> for VecRng<T, A>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I)
+    {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for item in iter
+        {
+            self.push_back(item);
+        }
+    }
+}
+/* same per-element caveat as the `Extend<T>` impl above; prefer
+ * `extend_from_slice` when the source is a `&[T]`.
+ */
+impl<'a, T: Copy, A: Allocator> Extend<&'a T> for VecRng<T, A>
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I)
+    {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for item in iter
+        {
+            self.push_back(*item);
+        }
+    }
+}
+impl<T> FromIterator<T> for VecRng<T>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self
+    {
+        let mut ret = Self::new();
+        ret.extend(iter);
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::{cell::Cell, panic::{AssertUnwindSafe, catch_unwind}, rc::Rc};
+
+    #[test]
+    fn wraparound_push_pop_front_back()
+    {
+        let mut v = VecRng::<i32>::with_capacity(4);
+        v.push_back(1);
+        v.push_back(2);
+        v.push_back(3);
+        assert_eq!(v.pop_front(), Some(1));
+        /* hindex now sits at 1; `4` fills the last slot, and once hindex
+         * has moved on to 2, pushing `5` wraps `tail` past `buffer.len()`
+         * back around to index 0.
+         */
+        v.push_back(4);
+        assert_eq!(v.pop_front(), Some(2));
+        v.push_back(5);
+        v.push_front(0);
+        let collected: Vec<i32> = v.iter().copied().collect();
+        assert_eq!(collected, vec![0, 3, 4, 5]);
+        assert_eq!(v.pop_back(), Some(5));
+        assert_eq!(v.pop_back(), Some(4));
+        assert_eq!(v.pop_front(), Some(0));
+        assert_eq!(v.pop_front(), Some(3));
+        assert_eq!(v.pop_front(), None);
+    }
+
+    #[test]
+    fn grow_while_wrapped()
+    {
+        let mut v = VecRng::<i32>::with_capacity(4);
+        for i in (0..3).rev()
+        {
+            v.push_front(i);
+        }
+        /* filling from the front leaves `hindex` at 1; advance it so the
+         * next `push_back` wraps the ring, then push past the current
+         * capacity so `reserve` must `grow`/`relocate` a wrapped buffer.
+         */
+        assert_eq!(v.pop_front(), Some(0));
+        v.push_back(3);
+        for i in 4..16
+        {
+            v.push_back(i);
+        }
+        let collected: Vec<i32> = v.iter().copied().collect();
+        assert_eq!(collected, (1..16).collect::<Vec<i32>>());
+    }
+
+    struct PanicOnClone
+    {
+        id: usize,
+        clone_calls: Rc<Cell<usize>>,
+        drop_calls: Rc<Cell<usize>>,
+        panic_at: usize,
+    }
+    impl Clone for PanicOnClone
+    {
+        fn clone(&self) -> Self
+        {
+            let n = self.clone_calls.get();
+            self.clone_calls.set(n + 1);
+            if n == self.panic_at
+            {
+                panic!("simulated clone failure");
+            }
+            PanicOnClone
+            {
+                id: self.id,
+                clone_calls: self.clone_calls.clone(),
+                drop_calls: self.drop_calls.clone(),
+                panic_at: self.panic_at,
+            }
+        }
+    }
+    impl Drop for PanicOnClone
+    {
+        fn drop(&mut self)
+        {
+            self.drop_calls.set(self.drop_calls.get() + 1);
+        }
+    }
+
+    #[test]
+    fn clone_panic_mid_copy_drops_only_the_written_prefix()
+    {
+        let clone_calls = Rc::new(Cell::new(0));
+        let drop_calls = Rc::new(Cell::new(0));
+        let mut v = VecRng::<PanicOnClone>::new();
+        for id in 0..5
+        {
+            v.push_back(PanicOnClone
+            {
+                id,
+                clone_calls: clone_calls.clone(),
+                drop_calls: drop_calls.clone(),
+                panic_at: 2,
+            });
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| v.clone()));
+        assert!(result.is_err());
+        /* two clones were written into `new`'s buffer before the third
+         * panicked; the `ClonedPrefix` guard must drop exactly those two,
+         * neither leaking them nor double-dropping the un-cloned rest.
+         */
+        assert_eq!(drop_calls.get(), 2);
+        drop(v);
+    }
+
+    struct DropCounted(Rc<Cell<usize>>);
+    impl Drop for DropCounted
+    {
+        fn drop(&mut self)
+        {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn into_iter_drops_unyielded_remainder_exactly_once()
+    {
+        let counter = Rc::new(Cell::new(0));
+        let mut v = VecRng::<DropCounted>::with_capacity(4);
+        for _ in 0..3
+        {
+            v.push_back(DropCounted(counter.clone()));
+        }
+        /* force a wrap so `IntoIter` must walk both the head and back
+         * slices while dropping the remainder: the second `push_front`
+         * grows the buffer and wraps `hindex` to its last slot, and
+         * consuming from the back leaves one element in each slice.
+         */
+        v.pop_front();
+        v.push_front(DropCounted(counter.clone()));
+        v.push_front(DropCounted(counter.clone()));
+
+        let mut into_iter = v.into_iter();
+        into_iter.next_back();
+        into_iter.next_back();
+        drop(into_iter);
+
+        /* 1 dropped immediately by the forcing `pop_front`, 2 dropped
+         * immediately as each consumed `next_back()` result is discarded, and
+         * 2 dropped by `IntoIter`'s own `Drop` for the unyielded
+         * remainder: every constructed element is dropped exactly once.
+         */
+        assert_eq!(counter.get(), 5);
+    }
 }